@@ -23,12 +23,19 @@ use std::time::SystemTime;
 
 pub struct Canvas<'a> {
     framebuffer: Box::<Framebuffer<'a>>,
+    /// Set by `draw_image` whenever the pixels it just drew were already
+    /// error-diffused in software, so the *next* full refresh skips the
+    /// hardware dithering instead of re-processing already-quantized pixels.
+    /// Reset after every full refresh, since it only ever describes what's
+    /// currently on screen, not the canvas for the rest of the process.
+    software_dithered: bool,
 }
 
 impl<'a> Canvas<'a> {
     pub fn new() -> Self {
         Self {
             framebuffer: Box::new(Framebuffer::new("/dev/fb0")),
+            software_dithered: false,
         }
     }
 
@@ -48,17 +55,30 @@ impl<'a> Canvas<'a> {
         self.framebuffer_mut().fill_rect(Point2 { x: area.left as i32, y: area.top as i32 }, area.size(), color::WHITE);
     }
 
-    pub fn update_full(&mut self) {
+    /// Returns the EPDC update marker of this refresh, which can be passed to
+    /// `wait_for_refresh` to block until it has actually settled on screen.
+    pub fn update_full(&mut self) -> u32 {
+        let dither_mode = if self.software_dithered {
+            dither_mode::EPDC_FLAG_USE_DITHERING_PASSTHROUGH
+        }else {
+            dither_mode::EPDC_FLAG_USE_REMARKABLE_DITHER
+        };
+        // Only applies to the refresh we're about to trigger; whatever gets drawn
+        // next (e.g. a later, non-dithered screen) starts from a clean slate.
+        self.software_dithered = false;
+
         self.framebuffer_mut().full_refresh(
             waveform_mode::WAVEFORM_MODE_GC16,
             display_temp::TEMP_USE_REMARKABLE_DRAW,
-            dither_mode::EPDC_FLAG_USE_REMARKABLE_DITHER,
+            dither_mode,
             0,
             true
-        );
+        )
     }
-    
-    pub fn update_partial(&mut self, region: &mxcfb_rect) {
+
+    /// Returns the EPDC update marker of this refresh, which can be passed to
+    /// `wait_for_refresh` to block until it has actually settled on screen.
+    pub fn update_partial(&mut self, region: &mxcfb_rect) -> u32 {
         self.framebuffer_mut().partial_refresh(
             region,
             PartialRefreshMode::Async,
@@ -67,7 +87,20 @@ impl<'a> Canvas<'a> {
             dither_mode::EPDC_FLAG_USE_DITHERING_PASSTHROUGH,
             0, // See documentation on DRAWING_QUANT_BITS in libremarkable/framebuffer/common.rs
             false
-        );
+        )
+    }
+
+    /// Blocks until the EPDC reports that the update identified by `marker`
+    /// (as returned by `update_full`/`update_partial`) has settled, so a
+    /// caller can sequence a clear-then-draw pair without the two refreshes
+    /// racing and leaving ghosting behind.
+    pub fn wait_for_refresh(&mut self, marker: u32) {
+        self.framebuffer_mut().wait_refresh_complete(marker);
+    }
+
+    /// Measures the rendered size of `text` at `size` without drawing anything.
+    pub fn measure_text(&mut self, text: &str, size: f32) -> mxcfb_rect {
+        self.framebuffer_mut().draw_text(Point2 { x: 0.0, y: DISPLAYHEIGHT as f32 }, text.to_owned(), size, color::BLACK, true)
     }
 
     pub fn draw_text(&mut self, pos: Point2<Option<i32>>, text: &str, size: f32) -> mxcfb_rect {
@@ -118,39 +151,50 @@ impl<'a> Canvas<'a> {
     /// Normally taking alpha from a image, may result in call kinds of colors
     /// (often this is black) which might make the whole image seem broken.
     /// This composites it with a white background, removing all problems
-    /// caused by transparency.
+    /// caused by transparency. The result is true grayscale luminance, not
+    /// just the red channel, so `dither` can meaningfully error-diffuse it
+    /// down to the panel's 16 gray levels instead of leaving that to the
+    /// hardware's own (lower quality) dithering.
     /// On the reMarkable this tooks about 300ms for a full image (1404x1872)
     /// in some tests. Icons are usually below 100ms. Only a fraction of resizing.
-    fn to_rgb_with_white_bg(img: &image::DynamicImage) -> image::RgbImage {
+    fn to_rgb_with_white_bg(img: &image::DynamicImage, dither: bool) -> image::RgbImage {
         let start = SystemTime::now();
-        
-        let color_bg_gray = 1.0; // 1 = White ; 0 = Black // The background
 
         let rgba = img.to_rgba();
-        let mut rgb = img.to_rgb();
-        for (x, y, pixel) in rgba.enumerate_pixels() {
-            let color_pix = [pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0];
-            let color_alpha = (255 - pixel[3]) as f32 / 255.0;
-
-            let new_rgb_f32 = image::Rgb([
-                color_pix[0] * (1.0 - color_alpha) + color_bg_gray * color_alpha,
-                color_pix[0] * (1.0 - color_alpha) + color_bg_gray * color_alpha,
-                color_pix[0] * (1.0 - color_alpha) + color_bg_gray * color_alpha]);
-
-            let new_rgb_u8: image::Rgb<u8> = image::Rgb([
-                (new_rgb_f32[0] * 255.0) as u8,
-                (new_rgb_f32[0] * 255.0) as u8,
-                (new_rgb_f32[0] * 255.0) as u8]);
-            
-            rgb.put_pixel(x, y, new_rgb_u8);
+        let (width, height) = (rgba.width(), rgba.height());
+
+        // Composite onto white and convert to luminance, keeping an f32 working buffer
+        // so accumulated dithering error isn't truncated between pixels.
+        let mut luminance: Vec<f32> = rgba.pixels().map(|pixel| {
+            let alpha = pixel[3] as f32 / 255.0;
+            let r = pixel[0] as f32 * alpha + 255.0 * (1.0 - alpha);
+            let g = pixel[1] as f32 * alpha + 255.0 * (1.0 - alpha);
+            let b = pixel[2] as f32 * alpha + 255.0 * (1.0 - alpha);
+            0.299 * r + 0.587 * g + 0.114 * b
+        }).collect();
+
+        if dither {
+            floyd_steinberg_dither(&mut luminance, width, height);
+        }
+
+        let mut rgb = image::RgbImage::new(width, height);
+        for (pixel, value) in rgb.pixels_mut().zip(luminance.iter()) {
+            let v = value.round().max(0.0).min(255.0) as u8;
+            *pixel = image::Rgb([v, v, v]);
         }
 
         debug!("RGBImage -> RGBAImage took {:?}", start.elapsed().unwrap()); // Prints when env RUST_LOG=debug
         rgb
     }
 
-    pub fn draw_image(&mut self, pos: Point2<Option<i32>>, img: &image::DynamicImage, insert_white_background: bool) -> mxcfb_rect {
-        let rgb_img = if insert_white_background { Self::to_rgb_with_white_bg(img) } else { img.to_rgb() };
+    pub fn draw_image(&mut self, pos: Point2<Option<i32>>, img: &image::DynamicImage, insert_white_background: bool, dither: bool) -> mxcfb_rect {
+        let rgb_img = if insert_white_background { Self::to_rgb_with_white_bg(img, dither) } else { img.to_rgb() };
+        if insert_white_background && dither {
+            // The image is already error-diffused to the panel's gray levels, so the next
+            // full refresh must not let the hardware dither it again on top of that.
+            // (to_rgb_with_white_bg is the only path that actually dithers.)
+            self.software_dithered = true;
+        }
         let mut pos = pos;
         if pos.x.is_none() || pos.y.is_none() {
             if pos.x.is_none() {
@@ -188,4 +232,73 @@ impl<'a> Canvas<'a> {
         (pos.y as u32) >= hitbox.top && (pos.y as u32) < (hitbox.top + hitbox.height)
     }
 
+    /// Draws a centered, bordered dialog box with a title and a vertical
+    /// stack of buttons. Returns the hitboxes of the buttons in the same
+    /// order they were passed in, so callers can test taps with `is_hitting`.
+    pub fn draw_dialog(&mut self, title: &str, buttons: &[&str]) -> Vec<mxcfb_rect> {
+        let dialog_width = 700;
+        let dialog_height = 220 + buttons.len() as u32 * 110;
+        let dialog_pos = Point2 {
+            x: Some(DISPLAYWIDTH as i32 / 2 - dialog_width as i32 / 2),
+            y: Some(DISPLAYHEIGHT as i32 / 2 - dialog_height as i32 / 2),
+        };
+        let dialog_rect = mxcfb_rect {
+            left: dialog_pos.x.unwrap() as u32,
+            top: dialog_pos.y.unwrap() as u32,
+            width: dialog_width,
+            height: dialog_height,
+        };
+
+        self.clear_area(&dialog_rect);
+        self.draw_rect(dialog_pos, Vector2 { x: dialog_width, y: dialog_height }, 3);
+
+        let title_rect = self.draw_text(
+            Point2 { x: None, y: Some(dialog_pos.y.unwrap() + 50) },
+            title,
+            45.0,
+        );
+
+        let mut button_rects = Vec::with_capacity(buttons.len());
+        let mut next_y = title_rect.top as i32 + title_rect.height as i32 + 60;
+        for button in buttons {
+            let button_rect = self.draw_button(Point2 { x: None, y: Some(next_y) }, button, 35.0, 15, 40);
+            next_y = button_rect.top as i32 + button_rect.height as i32 + 30;
+            button_rects.push(button_rect);
+        }
+
+        button_rects
+    }
+
+}
+
+/// Standard Floyd-Steinberg error diffusion: quantizes each pixel in `values`
+/// (row-major 0-255 luminance) to the nearest of the reMarkable's 16 grayscale
+/// levels, diffusing the rounding error to not-yet-processed neighbors (7/16
+/// east, 3/16 south-west, 5/16 south, 1/16 south-east).
+fn floyd_steinberg_dither(values: &mut [f32], width: u32, height: u32) {
+    let width = width as i64;
+    let height = height as i64;
+    let at = |x: i64, y: i64| (y * width + x) as usize;
+
+    for y in 0..height {
+        for x in 0..width {
+            let old_value = values[at(x, y)];
+            let new_value = ((old_value / 17.0).round() * 17.0).max(0.0).min(255.0);
+            let error = old_value - new_value;
+            values[at(x, y)] = new_value;
+
+            if x + 1 < width {
+                values[at(x + 1, y)] += error * 7.0 / 16.0;
+            }
+            if y + 1 < height {
+                if x - 1 >= 0 {
+                    values[at(x - 1, y + 1)] += error * 3.0 / 16.0;
+                }
+                values[at(x, y + 1)] += error * 5.0 / 16.0;
+                if x + 1 < width {
+                    values[at(x + 1, y + 1)] += error * 1.0 / 16.0;
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
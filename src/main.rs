@@ -13,11 +13,13 @@ use libremarkable::{image, cgmath, device::{CURRENT_DEVICE, Model}};
 use nix::unistd::Pid;
 use nix::sys::signal::{self, Signal};
 use signal_hook;
+use std::collections::VecDeque;
 use std::env;
-use std::process::{Child, Command, exit, ExitStatus};
-use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio, exit, ExitStatus};
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::sync::mpsc::RecvTimeoutError;
 use std::time::{Duration, SystemTime};
-use std::thread::sleep;
 
 use clap::{Clap, crate_version, crate_authors};
 use env_logger;
@@ -27,6 +29,29 @@ mod canvas;
 
 use canvas::{Canvas, mxcfb_rect, Point2};
 
+/// Tracks whether the main loop is just displaying the app, is currently
+/// asking the user to confirm a corner-triggered quit, or is showing the
+/// captured output of a process that crashed on its own.
+enum AppState {
+    Running,
+    ConfirmingQuit { quit_rect: mxcfb_rect, cancel_rect: mxcfb_rect },
+    Crashed,
+}
+
+/// Whatever the main loop is waiting on: a touch event forwarded from evdev, or
+/// the one-shot report that the watched child process has exited. Merging both
+/// onto a single channel means the loop only ever has one blocking wait per
+/// iteration, instead of stacking a separately-timed wait for the process behind
+/// the wait for input.
+enum LoopEvent {
+    Input(InputEvent),
+    ProcessExited(ExitStatus),
+}
+
+/// How many of the most recent combined stdout/stderr lines of the child
+/// process are kept around to show if it crashes.
+const CRASH_LOG_LINES: usize = 20;
+
 const CORNER_SIZE: u32 = 100;
 const CORNER_BOTTOM_LEFT: mxcfb_rect = mxcfb_rect { top: DISPLAYHEIGHT as u32 - CORNER_SIZE, left: 0, width: CORNER_SIZE, height: CORNER_SIZE };
 const CORNER_BOTTOM_RIGHT: mxcfb_rect = mxcfb_rect { top: DISPLAYHEIGHT as u32 - CORNER_SIZE, left: DISPLAYWIDTH as u32 - CORNER_SIZE, width: CORNER_SIZE, height: CORNER_SIZE };
@@ -40,17 +65,103 @@ struct Opts {
     #[clap(long, short, about = "Path for icon to display")]
     icon: Option<String>,
 
-    #[clap(long, about = "Size of icon to display (squared)", default_value = "500")]
-    icon_size: u16,
+    #[clap(long, about = "Size of icon to display (squared)")]
+    icon_size: Option<u16>,
 
     #[clap(long, short, about = "App name to display")]
     name: Option<String>,
 
-    #[clap(about = "Full path to the executable")]
-    command: String,
-    
+    #[clap(long, about = "Load name, icon, command and args from a toltec-style launcher (.draft) file. Flags passed explicitly still take precedence over it.")]
+    from_file: Option<String>,
+
+    #[clap(about = "Full path to the executable. Can be omitted if given through --from-file.")]
+    command: Option<String>,
+
     #[clap(multiple = true, about = "Arguments for the executable")]
     args: Vec<String>,
+
+    #[clap(long, about = "Dither icons/custom images in software instead of relying on the display's own (lower quality) dithering")]
+    dither: bool,
+}
+
+/// A minimal key=value ini as used by toltec-style launcher `.draft` files,
+/// parsed to fill in whichever `Opts` fields weren't passed on the command line.
+struct DraftFile {
+    name: Option<String>,
+    icon: Option<String>,
+    icon_size: Option<u16>,
+    command: Option<String>,
+    args: Vec<String>,
+}
+
+impl DraftFile {
+    fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+
+        let mut draft = Self { name: None, icon: None, icon_size: None, command: None, args: vec![] };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => (key.trim(), value.trim()),
+                _ => {
+                    warn!("Ignoring malformed line in launcher file: {}", line);
+                    continue;
+                }
+            };
+
+            match key {
+                "name" => draft.name = Some(value.to_owned()),
+                "icon" => draft.icon = Some(value.to_owned()),
+                "iconSize" => draft.icon_size = value.parse().ok(),
+                "command" => draft.command = Some(value.to_owned()),
+                "args" => draft.args = split_shell_words(value),
+                _ => warn!("Ignoring unknown key \"{}\" in launcher file.", key),
+            }
+        }
+
+        Ok(draft)
+    }
+
+    /// Fills in whichever `opts` fields weren't already set explicitly on the command line.
+    fn apply_to(self, opts: &mut Opts) {
+        if opts.name.is_none() { opts.name = self.name; }
+        if opts.icon.is_none() { opts.icon = self.icon; }
+        if opts.icon_size.is_none() { opts.icon_size = self.icon_size; }
+        if opts.command.is_none() { opts.command = self.command; }
+        if opts.args.is_empty() { opts.args = self.args; }
+    }
+}
+
+/// A small, good-enough shell-style splitter: splits on whitespace while keeping
+/// single- or double-quoted substrings together (no escape sequence support).
+fn split_shell_words(input: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c.is_whitespace() => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            },
+            None => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
 }
 
 fn main() {
@@ -76,63 +187,142 @@ fn main() {
     signal_hook::flag::register(signal_hook::SIGTERM, Arc::clone(&sigterm_received)).expect("Failed to register SIGTERM handler.");
 
     // Parsing arguments
-    let opts: Opts = Opts::parse();
+    let mut opts: Opts = Opts::parse();
+
+    // Fill in whatever wasn't passed explicitly from the launcher file, if one was given
+    if let Some(draft_path) = opts.from_file.clone() {
+        match DraftFile::load(&draft_path) {
+            Ok(draft) => draft.apply_to(&mut opts),
+            Err(e) => {
+                error!("Failed to read launcher file \"{}\": {}", draft_path, e);
+                exit(1);
+            }
+        }
+    }
 
     // Argument validation
-    if opts.icon_size < 50 || opts.icon_size > 1404 {
+    let command = match opts.command.clone() {
+        Some(command) => command,
+        None => {
+            error!("No command given. Pass one directly or through --from-file.");
+            exit(1);
+        }
+    };
+    opts.icon_size = Some(opts.icon_size.unwrap_or(500));
+    if opts.icon_size.unwrap() < 50 || opts.icon_size.unwrap() > 1404 {
         error!("Icon size invalid. Must be between 50 and 1404!");
         exit(1);
     }
 
     // Find app name
-    let name = if let Some(app_name) = opts.name {
-        app_name.clone()
+    let name = if let Some(app_name) = opts.name.clone() {
+        app_name
     }else {
         warn!("No app name was provided. Using command instead.");
-        opts.command.clone()
+        command.clone()
     };
 
     // Start process
-    info!("Staring process \"{}\" with arguments: {:?}", &opts.command, &opts.args);
-    let mut proc = Command::new(&opts.command).args(&opts.args).spawn().unwrap();
+    info!("Staring process \"{}\" with arguments: {:?}", &command, &opts.args);
+    let mut proc = Command::new(&command)
+        .args(&opts.args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn().unwrap();
     info!("Process started");
 
+    // Keep the last CRASH_LOG_LINES lines of output around so they can be
+    // shown on screen if the process crashes, since there's no console to
+    // see them on otherwise.
+    let crash_log: Arc<Mutex<VecDeque<String>>> = Arc::new(Mutex::new(VecDeque::with_capacity(CRASH_LOG_LINES)));
+    spawn_output_reader(proc.stdout.take().unwrap(), Arc::clone(&crash_log));
+    spawn_output_reader(proc.stderr.take().unwrap(), Arc::clone(&crash_log));
+
+    // Shared so the watcher thread below can poll it without taking it away from
+    // the main thread, which still needs it directly to kill it on request.
+    let proc = Arc::new(Mutex::new(proc));
+
     // Draw screen
     let mut canvas = Canvas::new();
-    canvas.clear();
+    draw_screen(&mut canvas, &opts, &name);
 
-    if let Some(custom_image_path) = opts.custom_image {
-        draw_custom_image(&mut canvas, &custom_image_path);
-        warn!("Using a custom image will NOT display how to quit the app.");
-        warn!("To quit the app, touch both bottom corners.");
-    }else if let Some(icon_path) = opts.icon {
-        draw_base(&mut canvas);
-        draw_icon_and_name(&mut canvas, &name, opts.icon_size, &icon_path);
-    }else {
-        draw_base(&mut canvas);
-        draw_name(&mut canvas, &name);
-    }
-    canvas.update_full();
+    // Setting up gpio input and the process watcher, both funneled into the same
+    // channel (see `LoopEvent`) so the main loop below has only one thing to wait on.
+    let (loop_tx, loop_rx) = std::sync::mpsc::channel::<LoopEvent>();
 
-    // Setting up gpio input
     let (input_tx, input_rx) = std::sync::mpsc::channel::<InputEvent>();
     let mut ev_context = EvDevContext::new(InputDevice::Multitouch, input_tx);
     ev_context.start();
+    {
+        let loop_tx = loop_tx.clone();
+        std::thread::spawn(move || {
+            for input_event in input_rx {
+                if loop_tx.send(LoopEvent::Input(input_event)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
 
-    // Input loop and waiting for process to exit
-    let pause_duration = Duration::from_millis(150);
+    // Polls the child in the background and reports its exit as a single one-shot
+    // event on `loop_tx`, instead of the main loop blocking on wait_timeout_ms itself
+    // on top of waiting for input.
+    {
+        let proc = Arc::clone(&proc);
+        std::thread::spawn(move || {
+            loop {
+                let status = proc.lock().expect("Failed to lock child process").try_wait();
+                match status {
+                    Ok(Some(status)) => {
+                        let _ = loop_tx.send(LoopEvent::ProcessExited(status));
+                        return;
+                    },
+                    Ok(None) => {},
+                    Err(_) => return,
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        });
+    }
+
+    // Main loop. Blocks on the combined channel instead of busy-polling, so a touch
+    // or the process exiting is reacted to immediately; the timeout is only there
+    // to give us a regular chance to poll the SIGINT/SIGTERM flags.
+    let recv_timeout = Duration::from_millis(50);
     let mut last_status_rect: Option<mxcfb_rect> = None;
+    let mut app_state = AppState::Running;
     loop {
-        let before_input = SystemTime::now();
-
-        // Process input events
+        // Process queued events
         let mut was_press = false;
-        for input_event in input_rx.try_iter() {
-            if let InputEvent::MultitouchEvent { event: mt_event } = input_event {
-                if let MultitouchEvent::Press { .. } = mt_event {
-                    was_press = true;
+        // Position of the finger that actually just transitioned to pressed, taken
+        // straight from the Press event itself rather than scanned back out of the
+        // finger state map, since a second resting finger could otherwise shadow it.
+        let mut just_pressed_pos = None;
+        let mut process_exited = None;
+        match loop_rx.recv_timeout(recv_timeout) {
+            Ok(event) => {
+                match event {
+                    LoopEvent::Input(InputEvent::MultitouchEvent { event: MultitouchEvent::Press { finger } }) => {
+                        was_press = true;
+                        just_pressed_pos = Some(finger.pos);
+                    },
+                    LoopEvent::Input(_) => {},
+                    LoopEvent::ProcessExited(status) => process_exited = Some(status),
                 }
-            }
+                // Drain whatever else queued up while we were handling this one.
+                for event in loop_rx.try_iter() {
+                    match event {
+                        LoopEvent::Input(InputEvent::MultitouchEvent { event: MultitouchEvent::Press { finger } }) => {
+                            was_press = true;
+                            just_pressed_pos = Some(finger.pos);
+                        },
+                        LoopEvent::Input(_) => {},
+                        LoopEvent::ProcessExited(status) => process_exited = Some(status),
+                    }
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => panic!("Input thread disconnected unexpectedly."),
         }
 
         let fingers = match ev_context.state {
@@ -142,7 +332,7 @@ fn main() {
             _ => panic!("Unexpected!")
         };
 
-        let trigger_quit = if was_press && fingers.values().filter(|f| f.pressed).count() == 2 {
+        let corner_quit_gesture = if was_press && fingers.values().filter(|f| f.pressed).count() == 2 {
             let hitting_bottom_left = fingers.values().filter(|f| f.pressed).any(|f| Canvas::is_hitting(f.pos, CORNER_BOTTOM_LEFT));
             let hitting_bottom_right = fingers.values().filter(|f| f.pressed).any(|f| Canvas::is_hitting(f.pos, CORNER_BOTTOM_RIGHT));
 
@@ -150,54 +340,96 @@ fn main() {
         }else {
             false
         };
+        let tapped_pos = just_pressed_pos;
         drop(fingers); // Prevent mutex from being locked even when waiting
 
-
-        // Check if user requested quiting (using buttons or the terminal)
-        if (trigger_quit)
-            || sigint_received.load(Ordering::Relaxed) || sigterm_received.load(Ordering::Relaxed) {
-
-            info!("Termination requested by user. Killing {}...", &opts.command);
-            if let Some(rect) = last_status_rect { canvas.clear_area(&rect); }
-            last_status_rect = Some(canvas.draw_text(cgmath::Point2 { x: None, y: Some(1872 - 300)}, "Killing process...", 60.0));
-            canvas.update_partial(&last_status_rect.unwrap());
-
-            if let Err(e) = kill_process(&mut proc) {
-                error!("kill_process() failed: {}", e);
-                info!("The application will continue to run until either the process terminates or killing succeeds.");
-
-                canvas.clear_area(&last_status_rect.unwrap());
-                last_status_rect = Some(canvas.draw_text(cgmath::Point2 { x: None, y: Some(1872 - 300)}, &format!("Failed to kill {}", &opts.command), 60.0));
-                canvas.update_partial(&last_status_rect.unwrap());
+        // SIGINT/SIGTERM come from outside (e.g. the launcher or a terminal), not an
+        // accidental touch, so they always terminate immediately without confirmation.
+        if sigint_received.load(Ordering::Relaxed) || sigterm_received.load(Ordering::Relaxed) {
+            if matches!(app_state, AppState::Crashed) {
+                // The child is already reaped; there's nothing left to signal or wait
+                // on, so just quit instead of looping on terminate_process() forever.
+                info!("Termination requested, but the process had already exited. Quitting...");
+                canvas.clear();
+                canvas.update_full();
+                exit(0);
+            }
+            if terminate_process(&mut canvas, &proc, &command, &mut last_status_rect) {
                 continue;
             }
-
-            info!("Process was successfully killed. Exiting...");
-
-            // Clear screen
-            canvas.clear();
-            canvas.update_full();
-            exit(0);
         }
 
-        // Check for process self termination
-        if let Ok(status) = wait_termination(&mut proc, 50, true) {
-            log_exit_status(&status);
-            info!("Process exited by itself. Quitting...");
-            canvas.clear();
-            canvas.update_full();
-            exit(0);
+        match app_state {
+            AppState::Running => {
+                if corner_quit_gesture {
+                    info!("Both bottom corners touched. Asking for quit confirmation...");
+                    let button_rects = canvas.draw_dialog("Quit this app?", &["Quit", "Cancel"]);
+                    canvas.update_full();
+                    app_state = AppState::ConfirmingQuit { quit_rect: button_rects[0], cancel_rect: button_rects[1] };
+                }
+            },
+            AppState::ConfirmingQuit { quit_rect, cancel_rect } => {
+                if let Some(pos) = tapped_pos {
+                    if Canvas::is_hitting(pos, quit_rect) {
+                        if terminate_process(&mut canvas, &proc, &command, &mut last_status_rect) {
+                            continue;
+                        }
+                    }else if Canvas::is_hitting(pos, cancel_rect) {
+                        info!("Quit cancelled by user.");
+                        draw_screen(&mut canvas, &opts, &name);
+                        app_state = AppState::Running;
+                    }
+                }
+            },
+            AppState::Crashed => {
+                if corner_quit_gesture {
+                    info!("Exiting crash screen...");
+                    canvas.clear();
+                    canvas.update_full();
+                    exit(0);
+                }
+            },
         }
 
-        // Wait remaining pause time
-        let elapsed = before_input.elapsed().unwrap();
-        if elapsed < pause_duration {
-            sleep(pause_duration - elapsed);
+        // Reported by the watcher thread once the process exits on its own (it never
+        // fires again afterwards, so there's nothing to guard against in AppState::Crashed).
+        if let Some(status) = process_exited {
+            log_exit_status(&status);
+            if status.success() {
+                info!("Process exited by itself. Quitting...");
+                canvas.clear();
+                canvas.update_full();
+                exit(0);
+            }else {
+                info!("Process crashed. Showing captured output...");
+                draw_crash_screen(&mut canvas, &crash_log);
+                app_state = AppState::Crashed;
+            }
         }
     }
 }
 
 
+/// Clears the canvas and draws the screen as requested through `opts`
+/// (custom image, icon+name or name-only). Used both for the initial draw
+/// and to restore the screen after a quit confirmation is cancelled.
+fn draw_screen(canvas: &mut Canvas, opts: &Opts, name: &str) {
+    canvas.clear();
+
+    if let Some(custom_image_path) = &opts.custom_image {
+        draw_custom_image(canvas, custom_image_path, opts.dither);
+        warn!("Using a custom image will NOT display how to quit the app.");
+        warn!("To quit the app, touch both bottom corners.");
+    }else if let Some(icon_path) = &opts.icon {
+        draw_base(canvas);
+        draw_icon_and_name(canvas, name, opts.icon_size.unwrap_or(500), icon_path, opts.dither);
+    }else {
+        draw_base(canvas);
+        draw_name(canvas, name);
+    }
+    canvas.update_full();
+}
+
 fn draw_base(canvas: &mut Canvas) {
     // Draw centered text
     canvas.draw_text(cgmath::Point2 { x: None, y: Some(1872 - 30) }, "Touch both bottom corners to manually quit.", 35.0);
@@ -213,14 +445,14 @@ fn draw_name(canvas: &mut Canvas, name: &str) {
 }
 
 
-fn draw_icon_and_name(canvas: &mut Canvas, name: &str, icon_size: u16, icon_path: &str) {
+fn draw_icon_and_name(canvas: &mut Canvas, name: &str, icon_size: u16, icon_path: &str, dither: bool) {
     info!("Drawing icon and name screen...");
     let img_rect = match image::open(icon_path) {
         Ok(icon) => {
             let start = SystemTime::now();
             let resized = icon.resize(icon_size as u32, icon_size as u32, image::imageops::FilterType::Lanczos3);
             debug!("Resizing image took {:?}", start.elapsed().unwrap()); // Prints when env RUST_LOG=debug
-            canvas.draw_image(cgmath::Point2 { x: None /* Center */, y: None /* Center */ }, &resized, true)
+            canvas.draw_image(cgmath::Point2 { x: None /* Center */, y: None /* Center */ }, &resized, true, dither)
         },
         Err(e) => {
             error!("Failed to load icon: {}", e);
@@ -236,11 +468,90 @@ fn draw_icon_and_name(canvas: &mut Canvas, name: &str, icon_size: u16, icon_path
 }
 
 
-fn draw_custom_image(canvas: &mut Canvas, image_path: &str) {
+/// Spawns a thread that reads `reader` line by line and feeds it into `log`,
+/// dropping the oldest line once `CRASH_LOG_LINES` is reached. Used for both
+/// the child's stdout and stderr so the two interleave roughly as they occur.
+///
+/// Reads raw bytes and lossily converts each line instead of using
+/// `BufRead::lines()`, since that bails out (and permanently ends capture)
+/// the instant it hits a single non-UTF-8 byte, which is exactly the kind
+/// of garbled output a crashing process is likely to produce.
+fn spawn_output_reader<R: Read + Send + 'static>(reader: R, log: Arc<Mutex<VecDeque<String>>>) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let read = match reader.read_until(b'\n', &mut buf) {
+                Ok(read) => read,
+                Err(_) => break,
+            };
+            if read == 0 {
+                break; // EOF
+            }
+            while buf.last() == Some(&b'\n') || buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+            let line = String::from_utf8_lossy(&buf).into_owned();
+
+            let mut log = log.lock().expect("Failed to lock crash log");
+            if log.len() >= CRASH_LOG_LINES {
+                log.pop_front();
+            }
+            log.push_back(line);
+        }
+    });
+}
+
+/// Word-wraps `text` at whitespace so each resulting line renders no wider than
+/// `max_width` pixels at the given font `size`. Words are not broken: a single
+/// word wider than `max_width` stays whole on its own, wider line.
+fn wrap_text(canvas: &mut Canvas, text: &str, size: f32, max_width: u32) -> Vec<String> {
+    let mut lines = vec![];
+    for raw_line in text.lines() {
+        let mut current = String::new();
+        for word in raw_line.split_whitespace() {
+            let candidate = if current.is_empty() { word.to_owned() } else { format!("{} {}", current, word) };
+            if !current.is_empty() && canvas.measure_text(&candidate, size).width > max_width {
+                lines.push(current);
+                current = word.to_owned();
+            }else {
+                current = candidate;
+            }
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+/// Renders the captured stdout/stderr of a crashed process along with a
+/// prompt to quit, replacing the normal "is running" screen.
+fn draw_crash_screen(canvas: &mut Canvas, crash_log: &Arc<Mutex<VecDeque<String>>>) {
+    canvas.clear();
+
+    let margin = 20;
+    let font_size = 28.0;
+    let line_height = 34;
+    let mut y = margin;
+
+    let log = crash_log.lock().expect("Failed to lock crash log");
+    for line in log.iter() {
+        for wrapped_line in wrap_text(canvas, line, font_size, DISPLAYWIDTH as u32 - margin as u32 * 2) {
+            canvas.draw_text(cgmath::Point2 { x: Some(margin), y: Some(y) }, &wrapped_line, font_size);
+            y += line_height;
+        }
+    }
+    drop(log);
+
+    canvas.draw_text(cgmath::Point2 { x: None, y: Some(1872 - 60) }, "App crashed - touch corners to exit", 40.0);
+    canvas.update_full();
+}
+
+fn draw_custom_image(canvas: &mut Canvas, image_path: &str, dither: bool) {
     info!("Drawing custom icon screen...");
     match image::open(image_path) {
         Ok(img) => {
-            canvas.draw_image(cgmath::Point2 { x: None /* Center */, y: None /* Center */ }, &img, true);
+            canvas.draw_image(cgmath::Point2 { x: None /* Center */, y: None /* Center */ }, &img, true, dither);
         },
         Err(e) => {
             error!("Failed to load custom image: {}", e);
@@ -251,7 +562,53 @@ fn draw_custom_image(canvas: &mut Canvas, image_path: &str) {
 }
 
 
+/// Clears the previous status rect (if any) and draws `text` in its place,
+/// blocking on each refresh's EPDC marker so the clear has actually settled
+/// before the new text is drawn on top of it, instead of racing two async
+/// partial refreshes and leaving ghosting behind.
+fn set_status(canvas: &mut Canvas, last_status_rect: &mut Option<mxcfb_rect>, text: &str) {
+    if let Some(rect) = last_status_rect.take() {
+        canvas.clear_area(&rect);
+        let marker = canvas.update_partial(&rect);
+        canvas.wait_for_refresh(marker);
+    }
+
+    let rect = canvas.draw_text(cgmath::Point2 { x: None, y: Some(1872 - 300) }, text, 60.0);
+    let marker = canvas.update_partial(&rect);
+    canvas.wait_for_refresh(marker);
+    *last_status_rect = Some(rect);
+}
+
+/// Shows a "Killing process..." status and attempts to kill the child
+/// process, exiting the whole program on success. Returns `true` if killing
+/// failed, so the caller knows to keep looping and waiting instead of
+/// treating the request as handled.
+fn terminate_process(canvas: &mut Canvas, proc: &Arc<Mutex<Child>>, command: &str, last_status_rect: &mut Option<mxcfb_rect>) -> bool {
+    info!("Termination requested by user. Killing {}...", command);
+    set_status(canvas, last_status_rect, "Killing process...");
+
+    let mut proc = proc.lock().expect("Failed to lock child process");
+    if let Err(e) = kill_process(&mut proc) {
+        error!("kill_process() failed: {}", e);
+        info!("The application will continue to run until either the process terminates or killing succeeds.");
+        set_status(canvas, last_status_rect, &format!("Failed to kill {}", command));
+        return true;
+    }
+
+    info!("Process was successfully killed. Exiting...");
+    canvas.clear();
+    canvas.update_full();
+    exit(0);
+}
+
 fn kill_process(child: &mut Child) -> Result<(), Box<dyn std::error::Error>> {
+    // The process may already have exited (e.g. it was reaped by the self-termination
+    // check just before this was called), in which case there's nothing left to signal.
+    if let Ok(Some(status)) = child.try_wait() {
+        log_exit_status(&status);
+        return Ok(());
+    }
+
     let child_pid = Pid::from_raw(child.id() as i32);
     info!("Killing process gracefully...");
     signal::kill(child_pid, Signal::SIGINT)?;